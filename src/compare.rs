@@ -0,0 +1,236 @@
+use tiny_skia::{FilterQuality, Pixmap, PixmapPaint, Transform};
+
+use crate::renderer::decode_pixmap;
+use crate::{OutputFormat, RenderedDocument, RenderedPage};
+
+/// A rectangular region (in pixels) of a page where two renders disagreed.
+#[derive(Copy, Clone, Debug)]
+pub struct MismatchBox {
+    /// The x coordinate of the top-left corner.
+    pub x: u32,
+    /// The y coordinate of the top-left corner.
+    pub y: u32,
+    /// The width of the region.
+    pub width: u32,
+    /// The height of the region.
+    pub height: u32,
+}
+
+/// Options controlling how two rendered pages are compared.
+#[derive(Copy, Clone)]
+pub struct DiffOptions {
+    /// Per-channel (R/G/B/A) deltas at or below this value are ignored, so
+    /// that sub-pixel anti-aliasing differences between renderers don't get
+    /// flagged as real mismatches.
+    pub channel_tolerance: u8,
+    /// The minimum similarity percentage (in the range `0.0..=100.0`) a page
+    /// needs to reach to be considered in agreement.
+    pub similarity_threshold: f32,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            channel_tolerance: 16,
+            similarity_threshold: 99.0,
+        }
+    }
+}
+
+/// The result of comparing a single page between two renders.
+pub struct PageDiff {
+    /// The number of pixels whose color differs by more than the configured
+    /// tolerance.
+    pub mismatched_pixels: u32,
+    /// The total number of pixels that were compared.
+    pub total_pixels: u32,
+    /// The smallest rectangle enclosing all mismatching pixels, or `None` if
+    /// the pages are identical within tolerance.
+    pub bounding_box: Option<MismatchBox>,
+    /// The percentage (`0.0..=100.0`) of pixels that agreed within tolerance.
+    pub similarity: f32,
+    /// Whether `similarity` falls below the configured threshold.
+    pub disagrees: bool,
+    /// A visualization of the diff: the dimmed first page with mismatching
+    /// pixels painted in a high-contrast color.
+    pub heatmap: Pixmap,
+}
+
+/// The result of comparing a whole document (all of its pages) between two
+/// renders.
+pub enum DocumentDiff {
+    /// The documents don't even have the same number of pages, so no
+    /// meaningful page-by-page diff can be produced. This is always treated
+    /// as a disagreement.
+    PageCountMismatch {
+        /// The number of pages in the first document.
+        a_pages: usize,
+        /// The number of pages in the second document.
+        b_pages: usize,
+    },
+    /// Every page was compared; see `PageDiff` for the per-page results.
+    Pages(Vec<PageDiff>),
+}
+
+impl DocumentDiff {
+    /// Whether any page disagrees beyond the configured threshold (or the
+    /// page counts didn't even match).
+    pub fn disagrees(&self) -> bool {
+        match self {
+            DocumentDiff::PageCountMismatch { .. } => true,
+            DocumentDiff::Pages(pages) => pages.iter().any(|page| page.disagrees),
+        }
+    }
+}
+
+/// Compare two rendered documents page by page. `format` must be the
+/// `OutputFormat` both documents were rendered with.
+pub fn diff_documents(
+    a: &RenderedDocument,
+    b: &RenderedDocument,
+    format: OutputFormat,
+    options: &DiffOptions,
+) -> Result<DocumentDiff, String> {
+    if a.len() != b.len() {
+        return Ok(DocumentDiff::PageCountMismatch {
+            a_pages: a.len(),
+            b_pages: b.len(),
+        });
+    }
+
+    let pages = a
+        .iter()
+        .zip(b.iter())
+        .map(|(page_a, page_b)| diff_pages(page_a, page_b, format, options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(DocumentDiff::Pages(pages))
+}
+
+/// Compare a single page (as raster bytes in the given `OutputFormat`)
+/// between two renders.
+pub fn diff_pages(
+    a: &RenderedPage,
+    b: &RenderedPage,
+    format: OutputFormat,
+    options: &DiffOptions,
+) -> Result<PageDiff, String> {
+    let pixmap_a = decode_pixmap(a, format).map_err(|e| format!("unable to decode page a as a pixmap: {}", e))?;
+    let pixmap_b = decode_pixmap(b, format).map_err(|e| format!("unable to decode page b as a pixmap: {}", e))?;
+
+    Ok(diff_pixmaps(&pixmap_a, &pixmap_b, options))
+}
+
+/// Compare N renders of the same document against a single reference render,
+/// yielding one `DocumentDiff` per entry in `documents` (including a trivial,
+/// always-agreeing entry for the reference itself). `format` must be the
+/// `OutputFormat` every document was rendered with.
+pub fn diff_against_reference(
+    documents: &[RenderedDocument],
+    reference_index: usize,
+    format: OutputFormat,
+    options: &DiffOptions,
+) -> Result<Vec<DocumentDiff>, String> {
+    let reference = documents
+        .get(reference_index)
+        .ok_or_else(|| "reference_index out of bounds".to_string())?;
+
+    documents
+        .iter()
+        .map(|document| diff_documents(reference, document, format, options))
+        .collect()
+}
+
+fn diff_pixmaps(a: &Pixmap, b: &Pixmap, options: &DiffOptions) -> PageDiff {
+    let width = a.width().max(b.width());
+    let height = a.height().max(b.height());
+
+    let a = normalize(a, width, height);
+    let b = normalize(b, width, height);
+
+    let mut heatmap = dim(&a);
+
+    let mut mismatched_pixels = 0u32;
+    let mut min_x = u32::MAX;
+    let mut min_y = u32::MAX;
+    let mut max_x = 0u32;
+    let mut max_y = 0u32;
+
+    for y in 0..height {
+        for x in 0..width {
+            let offset = ((y * width + x) * 4) as usize;
+            let pixel_a = &a.data()[offset..offset + 4];
+            let pixel_b = &b.data()[offset..offset + 4];
+
+            let disagrees = pixel_a
+                .iter()
+                .zip(pixel_b.iter())
+                .any(|(&ca, &cb)| ca.abs_diff(cb) > options.channel_tolerance);
+
+            if disagrees {
+                mismatched_pixels += 1;
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x);
+                max_y = max_y.max(y);
+
+                heatmap.data_mut()[offset..offset + 4].copy_from_slice(&[255, 0, 255, 255]);
+            }
+        }
+    }
+
+    let total_pixels = width * height;
+    let similarity = 100.0 * (1.0 - mismatched_pixels as f32 / total_pixels as f32);
+
+    let bounding_box = (mismatched_pixels > 0).then_some(MismatchBox {
+        x: min_x,
+        y: min_y,
+        width: max_x - min_x + 1,
+        height: max_y - min_y + 1,
+    });
+
+    PageDiff {
+        mismatched_pixels,
+        total_pixels,
+        bounding_box,
+        similarity,
+        disagrees: similarity < options.similarity_threshold,
+        heatmap,
+    }
+}
+
+/// Scale `pixmap` up to fit within a `width`x`height` canvas using bilinear
+/// filtering, so two renders at different native resolutions can be
+/// compared pixel-for-pixel. Scaling is uniform (the same factor on both
+/// axes), so a pixmap whose aspect ratio doesn't match the target leaves
+/// part of the canvas blank instead of being stretched to fill it — a
+/// mismatched aspect ratio should show up as a meaningful diff, not be
+/// silently distorted away.
+fn normalize(pixmap: &Pixmap, width: u32, height: u32) -> Pixmap {
+    if pixmap.width() == width && pixmap.height() == height {
+        return pixmap.clone();
+    }
+
+    let mut scaled = Pixmap::new(width, height).unwrap();
+    let paint = PixmapPaint {
+        quality: FilterQuality::Bilinear,
+        ..Default::default()
+    };
+    let scale = (width as f32 / pixmap.width() as f32).min(height as f32 / pixmap.height() as f32);
+    let transform = Transform::from_scale(scale, scale);
+
+    scaled.draw_pixmap(0, 0, pixmap.as_ref(), &paint, transform, None);
+    scaled
+}
+
+/// Dim a pixmap so that pixels painted on top of it (to highlight a
+/// mismatch) stand out clearly.
+fn dim(pixmap: &Pixmap) -> Pixmap {
+    let mut dimmed = pixmap.clone();
+
+    for channel in dimmed.data_mut().iter_mut() {
+        *channel = (*channel as f32 * 0.35) as u8;
+    }
+
+    dimmed
+}
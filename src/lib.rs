@@ -63,11 +63,19 @@ Note that this crate isn't in the best "shape" in terms of structure and documen
 for this is that I mainly use it for personal purposes, so I didn't put a lot of effort into cleaning
 it up, and that's why it's also not released on crates.io. Nevertheless, it should still work fine
 for anyone who has the exact need of rendering a PDF with different backends.
+
+The `pdfium`/`quartz`/`pdf.js` helper binaries each take their arguments positionally, and that
+positional list has grown over time (output format, password, page range). If you're rebuilding one
+of these helpers from `src/pdfium`/`src/quartz`/`src/pdfjs`, make sure it accepts the same arguments
+in the same order as the corresponding `render_*` function in `renderer.rs` passes them, or the
+options you set on `RenderOptions` will be silently ignored by that backend.
 */
 
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 #![allow(dead_code)]
 
+mod compare;
 mod renderer;
+pub use compare::*;
 pub use renderer::*;
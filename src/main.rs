@@ -1,4 +1,4 @@
-use crate::renderer::{RenderOptions, Renderer};
+use crate::renderer::{RenderOptions, Renderer, Sizing};
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
 use std::path::{Path, PathBuf};
@@ -10,15 +10,21 @@ mod renderer;
 fn main() {
     let _ = std::fs::remove_dir_all("test");
 
-    let renderers: Vec<Renderer> = vec![
-        // Renderer::Mupdf,
-        Renderer::Ghostscript,
-        Renderer::Pdfium,
-        // Renderer::Poppler,
-        Renderer::Quartz,
-        // Renderer::Pdfjs,
-        // Renderer::Pdfbox,
-    ];
+    let mut renderers: Vec<Renderer> = Vec::new();
+    // #[cfg(feature = "mupdf")]
+    // renderers.push(Renderer::Mupdf);
+    #[cfg(feature = "ghostscript")]
+    renderers.push(Renderer::Ghostscript);
+    #[cfg(feature = "pdfium")]
+    renderers.push(Renderer::Pdfium);
+    // #[cfg(feature = "poppler")]
+    // renderers.push(Renderer::Poppler);
+    #[cfg(feature = "quartz")]
+    renderers.push(Renderer::Quartz);
+    // #[cfg(feature = "pdfjs")]
+    // renderers.push(Renderer::Pdfjs);
+    // #[cfg(feature = "pdfbox")]
+    // renderers.push(Renderer::Pdfbox);
 
     let root_dir = Path::new("/Users/lstampfl/Programming/GitHub/typst/tests/store/pdf");
     // let root_dir = Path::new("pdf");
@@ -42,7 +48,14 @@ fn main() {
                     renderer.name()
                 );
                 renderer
-                    .render_as_pixmap(&file, &RenderOptions { scale: 1.75 }, Some(1.0 / 50.0))
+                    .render_as_pixmap(
+                        &file,
+                        &RenderOptions {
+                            sizing: Sizing::Scale(1.75),
+                            ..Default::default()
+                        },
+                        Some(1.0 / 50.0),
+                    )
                     .unwrap()
             })
             .collect::<Vec<_>>();
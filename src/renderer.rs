@@ -1,6 +1,7 @@
 use std::cmp::min;
 use std::fs::File;
 use std::io::Write;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
 use std::sync::Arc;
@@ -11,16 +12,216 @@ use tiny_skia::{Paint, PathBuilder, Pixmap, PixmapPaint, Stroke, Transform};
 use hayro::{render, Pdf, RenderSettings};
 use hayro_interpret::InterpreterSettings;
 
-/// The options that should be applied when rendering a PDF to a pixmap.
+/// How the raster size of a rendered page should be determined.
+///
+/// `render_hayro` resolves the fit modes separately for each page. The CLI
+/// backends resolve them to a single scale from the size of the document's
+/// first page (page 0, regardless of `page_range`) and apply that scale to
+/// every rendered page, so pages of a different size do not hit the
+/// requested width or box exactly.
+#[derive(Copy, Clone)]
+pub enum Sizing {
+    /// Scale the page's native size by this factor.
+    Scale(f32),
+    /// Scale the page so that it's exactly `width` pixels wide, preserving
+    /// its aspect ratio.
+    FitWidth(u32),
+    /// Scale the page down (or up) so that it fits within a `width`x`height`
+    /// pixel box, preserving its aspect ratio.
+    FitBox {
+        /// The width of the bounding box, in pixels.
+        width: u32,
+        /// The height of the bounding box, in pixels.
+        height: u32,
+    },
+}
+
+impl Default for Sizing {
+    fn default() -> Self {
+        Sizing::Scale(1.0)
+    }
+}
+
+/// The raster output format a backend should produce.
+#[derive(Copy, Clone)]
+pub enum OutputFormat {
+    /// PNG.
+    Png,
+    /// JPEG, at the given quality (`0`, worst, to `100`, best).
+    Jpeg {
+        /// The JPEG quality, from `0` to `100`.
+        quality: u8,
+    },
+    /// TIFF.
+    Tiff,
+    /// Raw PPM.
+    Ppm,
+}
+
+impl Default for OutputFormat {
+    fn default() -> Self {
+        OutputFormat::Png
+    }
+}
+
+impl OutputFormat {
+    /// The file extension (without a leading dot) produced by this format,
+    /// matching what poppler's `pdftoppm` itself names its output as.
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg { .. } => "jpg",
+            OutputFormat::Tiff => "tif",
+            OutputFormat::Ppm => "ppm",
+        }
+    }
+}
+
+/// A rectangular region of a page, in PDF points (1/72 inch), with the
+/// origin at the page's bottom-left corner.
 #[derive(Copy, Clone)]
+pub struct Rect {
+    /// The x coordinate of the rectangle's bottom-left corner.
+    pub x: f32,
+    /// The y coordinate of the rectangle's bottom-left corner.
+    pub y: f32,
+    /// The width of the rectangle.
+    pub width: f32,
+    /// The height of the rectangle.
+    pub height: f32,
+}
+
+/// The options that should be applied when rendering a PDF to a pixmap.
+#[derive(Clone)]
 pub struct RenderOptions {
-    /// By how much the original size should be scaled.
-    pub scale: f32,
+    /// How the page's raster size should be determined.
+    pub sizing: Sizing,
+    /// Only render this (inclusive, 0-indexed) range of pages, instead of
+    /// the whole document. Useful for cheaply generating a single
+    /// thumbnail, or for rendering a handful of pages out of a large one.
+    pub page_range: Option<RangeInclusive<usize>>,
+    /// Only render this rectangle of each selected page, in PDF points, at
+    /// the resolution implied by `sizing`. Useful for zooming into a single
+    /// glyph or vector detail at high DPI without rasterizing the whole
+    /// page at that resolution.
+    pub clip: Option<Rect>,
+    /// The raster format the backend should emit.
+    pub format: OutputFormat,
+    /// The owner password to use for encrypted PDFs, if any.
+    pub owner_password: Option<String>,
+    /// The user password to use for encrypted PDFs, if any.
+    pub user_password: Option<String>,
 }
 
 impl Default for RenderOptions {
     fn default() -> Self {
-        Self { scale: 1.0 }
+        Self {
+            sizing: Sizing::default(),
+            page_range: None,
+            clip: None,
+            format: OutputFormat::default(),
+            owner_password: None,
+            user_password: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Only render the first page, for cheaply generating a single
+    /// thumbnail. Shorthand for `page_range: Some(0..=0)`.
+    pub fn first_page_only(mut self) -> Self {
+        self.page_range = Some(0..=0);
+        self
+    }
+
+    /// The password that should be used to unlock the document, preferring
+    /// the user password over the owner password if both are set. Backends
+    /// that only support a single password flag should use this.
+    fn password(&self) -> Option<&str> {
+        self.user_password
+            .as_deref()
+            .or(self.owner_password.as_deref())
+    }
+
+    /// The start/end of `page_range`, as strings, for helper binaries that
+    /// take them as separate positional arguments. `-1` signals "unset" on
+    /// the end (render through the last page); the start defaults to `0`.
+    fn page_range_args(&self) -> (String, String) {
+        match &self.page_range {
+            Some(range) => (range.start().to_string(), range.end().to_string()),
+            None => ("0".to_string(), "-1".to_string()),
+        }
+    }
+
+    /// Resolve `sizing` to a concrete scale factor (relative to the PDF's
+    /// native size at 72 DPI), probing the first page's native dimensions
+    /// from `buf` if a fit mode was requested.
+    fn effective_scale(&self, buf: &[u8]) -> Result<f32, String> {
+        match self.sizing {
+            Sizing::Scale(scale) => Ok(scale),
+            Sizing::FitWidth(width) => {
+                let (native_width, _) = probe_page_size(buf, self.password(), 0)?;
+                Ok(width as f32 / native_width)
+            }
+            Sizing::FitBox { width, height } => {
+                let (native_width, native_height) = probe_page_size(buf, self.password(), 0)?;
+                Ok((width as f32 / native_width).min(height as f32 / native_height))
+            }
+        }
+    }
+}
+
+/// Parse `buf` as a PDF and return the native size (in points) of the page at
+/// `page_index`, used to translate a `Sizing::FitWidth`/`Sizing::FitBox`
+/// request into a concrete scale factor, or a `clip` rectangle into pixels.
+fn probe_page_size(buf: &[u8], password: Option<&str>, page_index: usize) -> Result<(f32, f32), String> {
+    let pdf = Pdf::new(Arc::new(buf.to_vec()), password)
+        .map_err(|e| format!("failed to parse PDF: {:?}", e))?;
+    let page = pdf
+        .pages()
+        .get(page_index)
+        .ok_or_else(|| "page_index out of bounds".to_string())?;
+
+    Ok((page.width(), page.height()))
+}
+
+/// The detected format of an input buffer passed to a render function.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum InputKind {
+    /// A PDF document.
+    Pdf,
+    /// PostScript.
+    PostScript,
+    /// Encapsulated PostScript: PostScript restricted to a single page with
+    /// a `%%BoundingBox` comment describing its extent.
+    Eps,
+}
+
+impl InputKind {
+    /// Detect the format of `buf` from its magic bytes.
+    pub fn detect(buf: &[u8]) -> InputKind {
+        if buf.starts_with(b"%PDF") {
+            return InputKind::Pdf;
+        }
+
+        let header = &buf[..buf.len().min(2048)];
+        if header.starts_with(b"%!PS") {
+            let is_eps = header.windows(4).any(|w| w == b"EPSF")
+                || header.windows(13).any(|w| w == b"%%BoundingBox");
+            return if is_eps { InputKind::Eps } else { InputKind::PostScript };
+        }
+
+        InputKind::Pdf
+    }
+
+    /// The file extension (without a leading dot) conventionally used for
+    /// this input format.
+    fn extension(&self) -> &'static str {
+        match self {
+            InputKind::Pdf => "pdf",
+            InputKind::PostScript => "ps",
+            InputKind::Eps => "eps",
+        }
     }
 }
 
@@ -35,20 +236,28 @@ pub type RenderedDocument = Vec<RenderedPage>;
 #[allow(dead_code)] // Variants are constructed via string matching in pdf-validate
 pub enum Renderer {
     /// The pdfium renderer.
+    #[cfg(feature = "pdfium")]
     Pdfium,
     /// The mupdf renderer.
+    #[cfg(feature = "mupdf")]
     Mupdf,
     /// The poppler renderer.
+    #[cfg(feature = "poppler")]
     Poppler,
     /// The quartz renderer.
+    #[cfg(feature = "quartz")]
     Quartz,
     /// The pdf.js renderer.
+    #[cfg(feature = "pdfjs")]
     Pdfjs,
     /// The pdfbox renderer.
+    #[cfg(feature = "pdfbox")]
     Pdfbox,
     /// The ghostscript renderer.
+    #[cfg(feature = "ghostscript")]
     Ghostscript,
-    /// The hayro renderer (pure Rust).
+    /// The hayro renderer (pure Rust). Always available, since it doesn't
+    /// depend on an external binary.
     Hayro,
 }
 
@@ -56,12 +265,19 @@ impl Renderer {
     /// Get the name of the renderer.
     pub fn name(&self) -> String {
         match self {
+            #[cfg(feature = "pdfium")]
             Renderer::Pdfium => "pdfium".to_string(),
+            #[cfg(feature = "mupdf")]
             Renderer::Mupdf => "mupdf".to_string(),
+            #[cfg(feature = "poppler")]
             Renderer::Poppler => "poppler".to_string(),
+            #[cfg(feature = "quartz")]
             Renderer::Quartz => "quartz".to_string(),
+            #[cfg(feature = "pdfjs")]
             Renderer::Pdfjs => "pdfjs".to_string(),
+            #[cfg(feature = "pdfbox")]
             Renderer::Pdfbox => "pdfbox".to_string(),
+            #[cfg(feature = "ghostscript")]
             Renderer::Ghostscript => "ghostscript".to_string(),
             Renderer::Hayro => "hayro".to_string(),
         }
@@ -69,17 +285,86 @@ impl Renderer {
 
     pub(crate) fn color(&self) -> (u8, u8, u8) {
         match self {
+            #[cfg(feature = "pdfium")]
             Renderer::Pdfium => (79, 184, 35),
+            #[cfg(feature = "mupdf")]
             Renderer::Mupdf => (34, 186, 184),
+            #[cfg(feature = "poppler")]
             Renderer::Poppler => (227, 137, 20),
+            #[cfg(feature = "quartz")]
             Renderer::Quartz => (234, 250, 60),
+            #[cfg(feature = "pdfjs")]
             Renderer::Pdfjs => (48, 17, 207),
+            #[cfg(feature = "pdfbox")]
             Renderer::Pdfbox => (237, 38, 98),
+            #[cfg(feature = "ghostscript")]
             Renderer::Ghostscript => (235, 38, 218),
             Renderer::Hayro => (100, 149, 237), // cornflower blue
         }
     }
 
+    /// Whether this backend can actually be used: its Cargo feature is
+    /// enabled, its environment variable is set, and that variable points to
+    /// a binary (or jar) that exists on disk.
+    pub fn is_available(&self) -> bool {
+        match self {
+            #[cfg(feature = "pdfium")]
+            Renderer::Pdfium => binary_configured("PDFIUM_BIN"),
+            #[cfg(feature = "mupdf")]
+            Renderer::Mupdf => binary_configured("MUPDF_BIN"),
+            #[cfg(feature = "poppler")]
+            Renderer::Poppler => binary_configured("POPPLER_BIN"),
+            #[cfg(feature = "quartz")]
+            Renderer::Quartz => binary_configured("QUARTZ_BIN"),
+            #[cfg(feature = "pdfjs")]
+            Renderer::Pdfjs => binary_configured("PDFJS_BIN"),
+            #[cfg(feature = "pdfbox")]
+            Renderer::Pdfbox => binary_configured("PDFBOX_BIN"),
+            #[cfg(feature = "ghostscript")]
+            Renderer::Ghostscript => binary_configured("GHOSTSCRIPT_BIN"),
+            Renderer::Hayro => true,
+        }
+    }
+
+    /// Whether this backend can rasterize PostScript/EPS input directly, in
+    /// addition to PDF.
+    fn accepts_postscript_input(&self) -> bool {
+        match self {
+            #[cfg(feature = "ghostscript")]
+            Renderer::Ghostscript => true,
+            _ => false,
+        }
+    }
+
+    /// All renderer variants compiled into this build, regardless of
+    /// whether they're currently available.
+    fn all() -> Vec<Renderer> {
+        vec![
+            #[cfg(feature = "pdfium")]
+            Renderer::Pdfium,
+            #[cfg(feature = "mupdf")]
+            Renderer::Mupdf,
+            #[cfg(feature = "poppler")]
+            Renderer::Poppler,
+            #[cfg(feature = "quartz")]
+            Renderer::Quartz,
+            #[cfg(feature = "pdfjs")]
+            Renderer::Pdfjs,
+            #[cfg(feature = "pdfbox")]
+            Renderer::Pdfbox,
+            #[cfg(feature = "ghostscript")]
+            Renderer::Ghostscript,
+            Renderer::Hayro,
+        ]
+    }
+
+    /// All renderers that are both compiled in and available to use, so
+    /// callers can enumerate usable backends instead of panicking inside
+    /// `env::var(...).unwrap()`.
+    pub fn available() -> Vec<Renderer> {
+        Self::all().into_iter().filter(Renderer::is_available).collect()
+    }
+
     pub(crate) fn render_as_pixmap(
         &self,
         buf: &[u8],
@@ -87,21 +372,20 @@ impl Renderer {
         border_width: Option<f32>,
     ) -> Result<Vec<Pixmap>, String> {
         let pages = self.render_as_png(buf, options)?;
+        let decoded = pages
+            .iter()
+            .map(|page| decode_pixmap(page, options.format))
+            .collect::<Result<Vec<_>, _>>()?;
+
         let Some(border_width) = border_width else {
-            return pages
-                .iter()
-                .map(|page| {
-                    Pixmap::decode_png(page).map_err(|_| "unable to generate pixmap".to_string())
-                })
-                .collect();
+            return Ok(decoded);
         };
 
         let mut pixmaps = vec![];
 
-        for page in &pages {
-            let decoded = Pixmap::decode_png(page).unwrap();
-            let width = imagesize::blob_size(&page).unwrap().width as f32;
-            let height = imagesize::blob_size(&page).unwrap().height as f32;
+        for decoded in &decoded {
+            let width = decoded.width() as f32;
+            let height = decoded.height() as f32;
             let border_width = min(width as u32, height as u32) as f32 * border_width;
 
             let actual_width = width + border_width;
@@ -153,163 +437,386 @@ impl Renderer {
         buf: &[u8],
         options: &RenderOptions,
     ) -> Result<RenderedDocument, String> {
+        if !self.is_available() {
+            return Err(format!(
+                "{} is not available: its environment variable is unset or doesn't point to an existing binary",
+                self.name()
+            ));
+        }
+
+        let input_kind = InputKind::detect(buf);
+        if input_kind != InputKind::Pdf && !self.accepts_postscript_input() {
+            return Err(format!(
+                "{} only accepts PDF input, not {:?}",
+                self.name(),
+                input_kind
+            ));
+        }
+
         match self {
+            #[cfg(feature = "pdfium")]
             Renderer::Pdfium => render_pdfium(buf, options),
+            #[cfg(feature = "mupdf")]
             Renderer::Mupdf => render_mupdf(buf, options),
+            #[cfg(feature = "poppler")]
             Renderer::Poppler => render_poppler(buf, options),
+            #[cfg(feature = "quartz")]
             Renderer::Quartz => render_quartz(buf, options),
+            #[cfg(feature = "pdfjs")]
             Renderer::Pdfjs => render_pdfjs(buf, options),
+            #[cfg(feature = "pdfbox")]
             Renderer::Pdfbox => render_pdfbox(buf, options),
+            #[cfg(feature = "ghostscript")]
             Renderer::Ghostscript => render_ghostscript(buf, options),
             Renderer::Hayro => render_hayro(buf, options),
         }
     }
 }
 
+/// Whether the binary (or jar) pointed to by `env_var` is configured and
+/// exists on disk.
+fn binary_configured(env_var: &str) -> bool {
+    env::var(env_var)
+        .map(|path| Path::new(&path).exists())
+        .unwrap_or(false)
+}
+
 /// Render a PDF file using pdfium.
+#[cfg(feature = "pdfium")]
 pub fn render_pdfium(buf: &[u8], options: &RenderOptions) -> Result<RenderedDocument, String> {
+    let scale = options.effective_scale(buf)?;
+    let (start_page, end_page) = options.page_range_args();
+
     let command = |input_path: &Path, dir: &Path| {
         Command::new(env::var("PDFIUM_BIN").unwrap())
             .arg(&input_path)
-            .arg(PathBuf::from(dir).join("out-%d.png"))
-            .arg((options.scale).to_string())
+            .arg(PathBuf::from(dir).join(format!("out-%d.{}", options.format.extension())))
+            .arg(scale.to_string())
+            .arg(options.password().unwrap_or(""))
+            .arg(&start_page)
+            .arg(&end_page)
             .output()
             .map_err(|e| format!("{}: {}", "failed to run renderer", e))
     };
 
-    let out_file_pattern = r"(?m)out-(\d+).png";
+    let out_file_pattern = format!(r"(?m)out-(\d+)\.{}", options.format.extension());
 
-    render_via_cli(buf, command, out_file_pattern)
+    render_via_cli(buf, command, &out_file_pattern, "pdf")
 }
 /// Render a PDF file using ghostscript.
+#[cfg(feature = "ghostscript")]
 pub fn render_ghostscript(buf: &[u8], options: &RenderOptions) -> Result<RenderedDocument, String> {
+    let input_kind = InputKind::detect(buf);
+
+    // `effective_scale`'s fit modes probe the page size by parsing `buf` as
+    // a PDF, which PostScript/EPS input isn't, so only an absolute scale is
+    // supported for those input kinds.
+    if input_kind != InputKind::Pdf && !matches!(options.sizing, Sizing::Scale(_)) {
+        return Err(format!(
+            "{:?} input only supports Sizing::Scale, not a fit mode",
+            input_kind
+        ));
+    }
+
+    let scale = options.effective_scale(buf)?;
+
+    // Ghostscript's `-dFirstPage`/`-dLastPage` are 1-indexed, unlike our
+    // 0-indexed `page_range`.
+    let first_page = options.page_range.as_ref().map(|range| range.start() + 1);
+    let last_page = options.page_range.as_ref().map(|range| range.end() + 1);
+
+    let device = match options.format {
+        OutputFormat::Png => "png16m",
+        OutputFormat::Jpeg { .. } => "jpeg",
+        OutputFormat::Tiff => "tiff24nc",
+        OutputFormat::Ppm => "ppmraw",
+    };
+
     let command = |input_path: &Path, dir: &Path| {
-        Command::new(env::var("GHOSTSCRIPT_BIN").unwrap())
-            .arg("-dNOPAUSE")
-            .arg("-sDEVICE=png16m")
+        let mut cmd = Command::new(env::var("GHOSTSCRIPT_BIN").unwrap());
+        cmd.arg("-dNOPAUSE")
+            .arg(format!("-sDEVICE={}", device))
             .arg("-dGraphicsAlphaBits=4")
             .arg("-dTextAlphaBits=4")
-            .arg("-sDEVICE=png16m")
             .arg("-dBATCH")
-            .arg(format!("-r{}", (72.0 * options.scale).to_string()))
+            .arg(format!("-r{}", (72.0 * scale).to_string()))
             .arg(format!(
                 "-sOutputFile={}",
-                PathBuf::from(dir).join("out-%d.png").to_str().unwrap()
-            ))
-            .arg(&input_path)
+                PathBuf::from(dir)
+                    .join(format!("out-%d.{}", options.format.extension()))
+                    .to_str()
+                    .unwrap()
+            ));
+
+        if let OutputFormat::Jpeg { quality } = options.format {
+            cmd.arg(format!("-dJPEGQ={}", quality));
+        }
+
+        if let Some(password) = options.password() {
+            cmd.arg(format!("-sPDFPassword={}", password));
+        }
+
+        if let Some(first_page) = first_page {
+            cmd.arg(format!("-dFirstPage={}", first_page));
+        }
+        if let Some(last_page) = last_page {
+            cmd.arg(format!("-dLastPage={}", last_page));
+        }
+
+        if input_kind == InputKind::Eps {
+            // Crop the page to the EPS's own `%%BoundingBox` instead of
+            // ghostscript's default, oversized page.
+            cmd.arg("-dEPSCrop");
+        }
+
+        if let Some(clip) = &options.clip {
+            // Clip the imageable area to `clip` (in PDF points, origin
+            // bottom-left) before rasterizing, so only that rectangle ends
+            // up in the output. `-c ... -f` must come last: everything after
+            // `-f` is treated as the input file to run, not a switch.
+            cmd.arg("-c").arg(format!(
+                "<< /PageOffset [{} {}] >> setpagedevice << /PageSize [{} {}] >> setpagedevice",
+                -clip.x, -clip.y, clip.width, clip.height
+            ));
+            cmd.arg("-f");
+        }
+
+        cmd.arg(&input_path)
             .output()
             .map_err(|e| format!("{}: {}", "failed to run renderer", e))
     };
 
-    let out_file_pattern = r"(?m)out-(\d+).png";
+    let out_file_pattern = format!(r"(?m)out-(\d+)\.{}", options.format.extension());
 
-    render_via_cli(buf, command, out_file_pattern)
+    render_via_cli(buf, command, &out_file_pattern, input_kind.extension())
 }
 
 /// Render a PDF file using mupdf.
+#[cfg(feature = "mupdf")]
 pub fn render_mupdf(buf: &[u8], options: &RenderOptions) -> Result<RenderedDocument, String> {
+    let scale = options.effective_scale(buf)?;
+
+    // `mutool draw`'s page range argument is 1-indexed, unlike our
+    // 0-indexed `page_range`.
+    let page_range = options
+        .page_range
+        .as_ref()
+        .map(|range| format!("{}-{}", range.start() + 1, range.end() + 1));
+
     let command = |input_path: &Path, dir: &Path| {
-        Command::new(env::var("MUPDF_BIN").unwrap())
-            .arg("draw")
+        let mut cmd = Command::new(env::var("MUPDF_BIN").unwrap());
+        cmd.arg("draw")
             .arg("-q")
             .arg("-r")
-            .arg((72.0 * options.scale).to_string())
-            .arg("-o")
-            .arg(PathBuf::from(dir).join("out-%d.png"))
-            .arg(&input_path)
-            .output()
-            .map_err(|e| e.to_string())
+            .arg((72.0 * scale).to_string());
+
+        if let Some(password) = options.password() {
+            cmd.arg("-p").arg(password);
+        }
+
+        cmd.arg("-o")
+            .arg(PathBuf::from(dir).join(format!("out-%d.{}", options.format.extension())))
+            .arg(&input_path);
+
+        if let Some(page_range) = &page_range {
+            cmd.arg(page_range);
+        }
+
+        cmd.output().map_err(|e| e.to_string())
     };
 
-    let out_file_pattern = r"(?m)out-(\d+).png";
+    let out_file_pattern = format!(r"(?m)out-(\d+)\.{}", options.format.extension());
 
-    render_via_cli(buf, command, out_file_pattern)
+    render_via_cli(buf, command, &out_file_pattern, "pdf")
 }
 
 /// Render a PDF file using poppler.
+///
+/// `pdftoppm` applies `clip` as a single pixel rectangle to every page it
+/// renders, so a `clip` only makes sense alongside a `page_range` that
+/// selects exactly one page (pages can have different sizes, which would
+/// make the same pixel rectangle mean something different on each one).
+#[cfg(feature = "poppler")]
 pub fn render_poppler(buf: &[u8], options: &RenderOptions) -> Result<RenderedDocument, String> {
+    let scale = options.effective_scale(buf)?;
+
+    if options.clip.is_some() && !matches!(&options.page_range, Some(range) if range.start() == range.end()) {
+        return Err("poppler only supports clip together with a single-page page_range".to_string());
+    }
+
+    // `-y` is measured from the top of the page, while `clip` is anchored to
+    // the page's bottom-left in PDF points, so flip it using that (sole)
+    // page's native height. The guard above ensures `page_range` selects
+    // exactly one page, so probe that page specifically rather than always
+    // page 0 — pages can have different sizes.
+    let clip_y_px = match &options.clip {
+        Some(clip) => {
+            let page_index = options.page_range.as_ref().map_or(0, |range| *range.start());
+            let (_, page_height) = probe_page_size(buf, options.password(), page_index)?;
+            Some(((page_height - clip.y - clip.height) * scale).round() as i64)
+        }
+        None => None,
+    };
+
     let command = |input_path: &Path, dir: &Path| {
-        Command::new(env::var("POPPLER_BIN").unwrap())
-            .arg("-r")
-            .arg((72.0 * options.scale).to_string())
-            .arg("-png")
-            .arg(&input_path)
+        let mut cmd = Command::new(env::var("POPPLER_BIN").unwrap());
+        cmd.arg("-r").arg((72.0 * scale).to_string());
+
+        if let Some(password) = &options.user_password {
+            cmd.arg("-upw").arg(password);
+        }
+        if let Some(password) = &options.owner_password {
+            cmd.arg("-opw").arg(password);
+        }
+
+        if let Some(range) = &options.page_range {
+            // pdftoppm's `-f`/`-l` are 1-indexed, unlike our 0-indexed
+            // `page_range`.
+            cmd.arg("-f").arg((range.start() + 1).to_string());
+            cmd.arg("-l").arg((range.end() + 1).to_string());
+        }
+
+        if let Some(clip) = &options.clip {
+            // pdftoppm's `-x`/`-y`/`-W`/`-H` are in pixels at the resolved DPI.
+            cmd.arg("-x").arg(((clip.x * scale).round() as i64).to_string());
+            cmd.arg("-y").arg(clip_y_px.unwrap().to_string());
+            cmd.arg("-W").arg(((clip.width * scale).round() as i64).to_string());
+            cmd.arg("-H").arg(((clip.height * scale).round() as i64).to_string());
+        }
+
+        match options.format {
+            OutputFormat::Png => {
+                cmd.arg("-png");
+            }
+            OutputFormat::Jpeg { .. } => {
+                cmd.arg("-jpeg");
+            }
+            OutputFormat::Tiff => {
+                cmd.arg("-tiff");
+            }
+            OutputFormat::Ppm => {
+                // pdftoppm emits raw PPM by default, with no flag needed.
+            }
+        }
+
+        cmd.arg(&input_path)
             .arg(PathBuf::from(dir).join("out"))
             .output()
             .map_err(|e| format!("{}: {}", "failed to run renderer", e))
     };
 
-    let out_file_pattern = r"(?m)-(\d+).png";
+    let out_file_pattern = format!(r"(?m)-(\d+)\.{}", options.format.extension());
 
-    render_via_cli(buf, command, out_file_pattern)
+    render_via_cli(buf, command, &out_file_pattern, "pdf")
 }
 
 /// Render a PDF file using quartz.
+#[cfg(feature = "quartz")]
 pub fn render_quartz(buf: &[u8], options: &RenderOptions) -> Result<RenderedDocument, String> {
+    let scale = options.effective_scale(buf)?;
+    let (start_page, end_page) = options.page_range_args();
+
     let command = |input_path: &Path, dir: &Path| {
         Command::new(env::var("QUARTZ_BIN").unwrap())
             .arg(&input_path)
             .arg(&dir)
-            .arg(options.scale.to_string())
+            .arg(scale.to_string())
+            .arg(options.password().unwrap_or(""))
+            .arg(&start_page)
+            .arg(&end_page)
+            .arg(options.format.extension())
             .output()
             .map_err(|e| format!("{}: {}", "failed to run renderer", e))
     };
 
-    let out_file_pattern = r"(?m)-(\d+).png";
+    let out_file_pattern = format!(r"(?m)-(\d+)\.{}", options.format.extension());
 
-    render_via_cli(buf, command, out_file_pattern)
+    render_via_cli(buf, command, &out_file_pattern, "pdf")
 }
 
 /// Render a PDF file using pdf.js.
+#[cfg(feature = "pdfjs")]
 pub fn render_pdfjs(buf: &[u8], options: &RenderOptions) -> Result<RenderedDocument, String> {
+    let scale = options.effective_scale(buf)?;
+    let (start_page, end_page) = options.page_range_args();
+
     let command = |input_path: &Path, dir: &Path| {
         Command::new("node")
             .arg(env::var("PDFJS_BIN").unwrap())
             .arg(&input_path)
             .arg(&dir)
-            .arg(options.scale.to_string())
+            .arg(scale.to_string())
+            .arg(options.password().unwrap_or(""))
+            .arg(&start_page)
+            .arg(&end_page)
+            .arg(options.format.extension())
             .output()
             .map_err(|e| format!("{}: {}", "failed to run renderer", e))
     };
 
-    let out_file_pattern = r"(?m)-(\d+).png";
+    let out_file_pattern = format!(r"(?m)-(\d+)\.{}", options.format.extension());
 
-    render_via_cli(buf, command, out_file_pattern)
+    render_via_cli(buf, command, &out_file_pattern, "pdf")
 }
 
 /// Render a PDF file using pdfbox.
+#[cfg(feature = "pdfbox")]
 pub fn render_pdfbox(buf: &[u8], options: &RenderOptions) -> Result<RenderedDocument, String> {
+    if options.password().is_some() {
+        return Err("pdfbox does not support rendering password-protected PDFs".to_string());
+    }
+
+    if matches!(options.format, OutputFormat::Ppm) {
+        return Err("pdfbox does not support Ppm output".to_string());
+    }
+
+    let scale = options.effective_scale(buf)?;
+    let pdfbox_format = match options.format {
+        OutputFormat::Png => "png",
+        OutputFormat::Jpeg { .. } => "jpg",
+        OutputFormat::Tiff => "tiff",
+        OutputFormat::Ppm => unreachable!("handled above"),
+    };
+
     let command = |input_path: &Path, _: &Path| {
-        let res = Command::new("java")
-            .arg("-jar")
+        let mut cmd = Command::new("java");
+        cmd.arg("-jar")
             .arg(env::var("PDFBOX_BIN").unwrap())
             .arg("render")
             .arg("-format")
-            .arg("png")
+            .arg(pdfbox_format)
             .arg("-i")
             .arg(&input_path)
             .arg("-dpi")
-            .arg(format!("{}", 72.0 * options.scale))
-            .output()
-            .map_err(|e| format!("{}: {}", "failed to run renderer", e));
-        return res;
+            .arg(format!("{}", 72.0 * scale));
+
+        if let Some(range) = &options.page_range {
+            // pdfbox's render CLI takes 1-indexed pages, unlike our
+            // 0-indexed `page_range`.
+            cmd.arg("-startPage").arg((range.start() + 1).to_string());
+            cmd.arg("-endPage").arg((range.end() + 1).to_string());
+        }
+
+        cmd.output()
+            .map_err(|e| format!("{}: {}", "failed to run renderer", e))
     };
 
-    let out_file_pattern = r"(?m)-(\d+).png";
+    let out_file_pattern = format!(r"(?m)-(\d+)\.{}", options.format.extension());
 
-    render_via_cli(buf, command, out_file_pattern)
+    render_via_cli(buf, command, &out_file_pattern, "pdf")
 }
 
 fn render_via_cli<F>(
     buf: &[u8],
     command_fn: F,
     out_file_pattern: &str,
+    input_extension: &str,
 ) -> Result<RenderedDocument, String>
 where
     F: Fn(&Path, &Path) -> Result<Output, String>,
 {
     let dir = TempDir::new("sitro").unwrap();
-    let input_path = dir.path().join("file.pdf");
+    let input_path = dir.path().join(format!("file.{}", input_extension));
     let mut input_file = File::create(&input_path).unwrap();
     input_file.write(buf).unwrap();
 
@@ -350,21 +857,165 @@ where
 /// Render a PDF file using hayro (pure Rust).
 pub fn render_hayro(buf: &[u8], options: &RenderOptions) -> Result<RenderedDocument, String> {
     let data = Arc::new(buf.to_vec());
-    let pdf = Pdf::new(data).map_err(|e| format!("failed to parse PDF: {:?}", e))?;
+    let pdf = Pdf::new(data, options.password())
+        .map_err(|e| format!("failed to parse PDF: {:?}", e))?;
 
     let interpreter_settings = InterpreterSettings::default();
-    let render_settings = RenderSettings {
-        x_scale: options.scale,
-        y_scale: options.scale,
-        ..Default::default()
+
+    let pages = pdf.pages();
+    let start = options.page_range.as_ref().map_or(0, |range| *range.start());
+    let end = options
+        .page_range
+        .as_ref()
+        .map_or(pages.len().saturating_sub(1), |range| *range.end());
+    let selected_pages = if start > end {
+        &pages[..0]
+    } else {
+        &pages[start.min(pages.len())..(end + 1).min(pages.len())]
     };
 
-    let mut pages = Vec::new();
-    for page in pdf.pages().iter() {
+    let mut rendered = Vec::new();
+    for page in selected_pages {
+        let (x_scale, y_scale) = match options.sizing {
+            Sizing::Scale(scale) => (scale, scale),
+            Sizing::FitWidth(width) => {
+                let scale = width as f32 / page.width();
+                (scale, scale)
+            }
+            Sizing::FitBox { width, height } => {
+                let scale = (width as f32 / page.width()).min(height as f32 / page.height());
+                (scale, scale)
+            }
+        };
+
+        let render_settings = RenderSettings {
+            x_scale,
+            y_scale,
+            ..Default::default()
+        };
+
         let pixmap = render(page, &interpreter_settings, &render_settings);
-        let png_data = pixmap.into_png().map_err(|e| format!("PNG encoding failed: {:?}", e))?;
-        pages.push(png_data);
+        let pixmap = match &options.clip {
+            Some(clip) => crop_pixmap(&pixmap, clip, x_scale, y_scale),
+            None => pixmap,
+        };
+        rendered.push(encode_pixmap(pixmap, options.format)?);
+    }
+
+    Ok(rendered)
+}
+
+/// Crop `pixmap` to `clip` (given in PDF points, origin bottom-left), at the
+/// resolution implied by `x_scale`/`y_scale`.
+fn crop_pixmap(pixmap: &Pixmap, clip: &Rect, x_scale: f32, y_scale: f32) -> Pixmap {
+    let width = (clip.width * x_scale).round().max(1.0) as u32;
+    let height = (clip.height * y_scale).round().max(1.0) as u32;
+
+    // Pixmap coordinates have their origin at the top-left, while `clip` is
+    // anchored to the page's bottom-left, so flip the y axis.
+    let x = clip.x * x_scale;
+    let y = pixmap.height() as f32 - (clip.y + clip.height) * y_scale;
+
+    let mut cropped = Pixmap::new(width, height).unwrap();
+    cropped.draw_pixmap(
+        0,
+        0,
+        pixmap.as_ref(),
+        &PixmapPaint::default(),
+        Transform::from_translate(-x, -y),
+        None,
+    );
+    cropped
+}
+
+/// Encode a rendered pixmap in the requested output format.
+///
+/// Hayro (via tiny_skia) only knows how to encode PNG and raw PPM directly;
+/// Jpeg/Tiff output isn't available for this backend, so callers that need
+/// those formats should use one of the CLI backends instead.
+fn encode_pixmap(pixmap: Pixmap, format: OutputFormat) -> Result<Vec<u8>, String> {
+    match format {
+        OutputFormat::Png => pixmap.into_png().map_err(|e| format!("PNG encoding failed: {:?}", e)),
+        OutputFormat::Ppm => Ok(encode_ppm(&pixmap)),
+        OutputFormat::Jpeg { .. } | OutputFormat::Tiff => Err(format!(
+            "hayro does not support {} output",
+            format.extension()
+        )),
+    }
+}
+
+/// Encode a pixmap as a binary (P6) PPM image.
+fn encode_ppm(pixmap: &Pixmap) -> Vec<u8> {
+    let mut out = format!("P6\n{} {}\n255\n", pixmap.width(), pixmap.height()).into_bytes();
+
+    for pixel in pixmap.pixels() {
+        let pixel = pixel.demultiply();
+        out.push(pixel.red());
+        out.push(pixel.green());
+        out.push(pixel.blue());
+    }
+
+    out
+}
+
+/// Decode raster bytes produced by a backend (in the given `OutputFormat`)
+/// back into a `Pixmap`, so comparison and thumbnail-stacking code can work
+/// with any output format, not just PNG.
+///
+/// Jpeg/Tiff decoding is delegated to the `image` crate, which needs to be
+/// declared as a dependency in `Cargo.toml` alongside `tiny_skia`.
+pub(crate) fn decode_pixmap(bytes: &[u8], format: OutputFormat) -> Result<Pixmap, String> {
+    match format {
+        OutputFormat::Png => Pixmap::decode_png(bytes).map_err(|e| format!("PNG decoding failed: {:?}", e)),
+        OutputFormat::Ppm => decode_ppm(bytes),
+        OutputFormat::Jpeg { .. } | OutputFormat::Tiff => {
+            // Neither JPEG nor the `tiff24nc`/similar devices we ask for carry
+            // an alpha channel, so the straight RGBA the `image` crate
+            // produces is already opaque and therefore equal to premultiplied.
+            let decoded = image::load_from_memory(bytes)
+                .map_err(|e| format!("{} decoding failed: {}", format.extension(), e))?
+                .into_rgba8();
+
+            let mut pixmap = Pixmap::new(decoded.width(), decoded.height())
+                .ok_or_else(|| "decoded image has zero size".to_string())?;
+            pixmap.data_mut().copy_from_slice(decoded.as_raw());
+            Ok(pixmap)
+        }
+    }
+}
+
+/// Decode a binary (P6) PPM image, the inverse of `encode_ppm`.
+fn decode_ppm(bytes: &[u8]) -> Result<Pixmap, String> {
+    let mut parts = bytes.splitn(4, |&b| b == b'\n');
+    let magic = parts.next().ok_or("truncated PPM header")?;
+    if magic != b"P6" {
+        return Err(format!(
+            "unsupported PPM variant: {:?}",
+            String::from_utf8_lossy(magic)
+        ));
+    }
+
+    let dimensions = parts.next().ok_or("truncated PPM header")?;
+    let mut dimensions = std::str::from_utf8(dimensions)
+        .map_err(|_| "invalid PPM header".to_string())?
+        .split_whitespace();
+    let width: u32 = dimensions
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or("invalid PPM width")?;
+    let height: u32 = dimensions
+        .next()
+        .and_then(|s| s.parse().ok())
+        .ok_or("invalid PPM height")?;
+    let _maxval = parts.next().ok_or("truncated PPM header")?;
+    let data = parts.next().ok_or("truncated PPM pixel data")?;
+
+    let mut pixmap = Pixmap::new(width, height).ok_or_else(|| "PPM has zero size".to_string())?;
+    for (i, rgb) in data.chunks_exact(3).enumerate() {
+        let offset = i * 4;
+        pixmap.data_mut()[offset..offset + 3].copy_from_slice(rgb);
+        pixmap.data_mut()[offset + 3] = 255;
     }
 
-    Ok(pages)
+    Ok(pixmap)
 }